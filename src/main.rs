@@ -1,5 +1,8 @@
 use std::collections::HashMap;
 use std::fs;
+use std::io::{BufRead, BufReader};
+use std::os::fd::{AsFd, AsRawFd};
+use std::process::{Command, Stdio};
 use std::time::{Duration, Instant};
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
@@ -7,7 +10,12 @@ use std::sync::atomic::{AtomicBool, Ordering};
 use anyhow::{bail, Context, Result};
 use clap::Parser;
 use glob::glob;
+use indexmap::IndexMap;
+use mlua::Lua;
 use nix::unistd::geteuid;
+use once_cell::unsync::OnceCell;
+use wait_timeout::ChildExt;
+use nix::sys::epoll::{Epoll, EpollCreateFlags, EpollEvent, EpollFlags, EpollTimeout};
 use nix::sys::inotify::{
     AddWatchFlags,
     Inotify,
@@ -21,20 +29,109 @@ fn verbose() -> bool {
     VERBOSE.load(Ordering::SeqCst)
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 enum TriggerKind {
     SimpleFile,
+    PollFile,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+enum ValueType {
+    #[default]
+    String,
+    Integer,
+    Float,
+    Boolean,
+}
+
+/// Controls when an action re-fires as a trigger's value is re-read.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+enum TriggerSemantics {
+    /// Fire whenever the value differs from the last one seen (default).
+    #[default]
+    OnChange,
+    /// Like `on-change`, but never fire when there's no previous mapped
+    /// value to have "entered" from: on the very first reading of a
+    /// trigger, or right after a reading that matched no `value-map` entry.
+    OnEnter,
+    /// Fire on every poll/event, even if the value hasn't changed. Events
+    /// within 50ms of the trigger's last read are still skipped (see
+    /// `TriggerHandler::poll_and_name`).
+    Always,
+}
+
+impl TriggerSemantics {
+    fn should_fire(&self, prev: Option<&str>, current: &str) -> bool {
+        match self {
+            TriggerSemantics::Always => true,
+            TriggerSemantics::OnChange => prev != Some(current),
+            TriggerSemantics::OnEnter => prev.is_some() && prev != Some(current),
+        }
+    }
+}
+
+/// A single entry of a `value-map` once it's been keyed by a comparison
+/// instead of an exact string, e.g. `"<20"`, `">=80"` or `"40..=60"`.
+#[derive(Debug, Clone, Copy)]
+enum RangeExpr {
+    Lt(f64),
+    Le(f64),
+    Gt(f64),
+    Ge(f64),
+    Eq(f64),
+    Inclusive(f64, f64),
+}
+
+impl RangeExpr {
+    fn parse(key: &str) -> Option<Self> {
+        let key = key.trim();
+        if let Some(rest) = key.strip_prefix("<=") {
+            return rest.trim().parse().ok().map(RangeExpr::Le);
+        }
+        if let Some(rest) = key.strip_prefix(">=") {
+            return rest.trim().parse().ok().map(RangeExpr::Ge);
+        }
+        if let Some(rest) = key.strip_prefix('<') {
+            return rest.trim().parse().ok().map(RangeExpr::Lt);
+        }
+        if let Some(rest) = key.strip_prefix('>') {
+            return rest.trim().parse().ok().map(RangeExpr::Gt);
+        }
+        if let Some((lo, hi)) = key.split_once("..=") {
+            return Some(RangeExpr::Inclusive(lo.trim().parse().ok()?, hi.trim().parse().ok()?));
+        }
+
+        key.parse().ok().map(RangeExpr::Eq)
+    }
+
+    fn matches(&self, value: f64) -> bool {
+        match *self {
+            RangeExpr::Lt(n) => value < n,
+            RangeExpr::Le(n) => value <= n,
+            RangeExpr::Gt(n) => value > n,
+            RangeExpr::Ge(n) => value >= n,
+            RangeExpr::Eq(n) => value == n,
+            RangeExpr::Inclusive(lo, hi) => (lo..=hi).contains(&value),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 struct Trigger {
     name: String,
     #[serde(rename = "type")]
     kind: TriggerKind,
     file: PathBuf,
+    #[serde(rename = "value-type", default)]
+    value_type: ValueType,
     #[serde(rename = "value-map")]
-    map: HashMap<String, String>,
+    map: IndexMap<String, String>,
+    /// Only meaningful for `type = "poll-file"`: how often to re-read `file`.
+    #[serde(rename = "poll-interval", default, with = "humantime_serde::option")]
+    poll_interval: Option<Duration>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -44,14 +141,47 @@ enum Action {
         trigger: String,
         file: String,
         values: HashMap<String, String>,
+        #[serde(rename = "on-trigger", default)]
+        semantics: TriggerSemantics,
+    },
+    /// Runs a Lua chunk on each trigger match. `script` is compiled as a
+    /// vararg chunk and called with `(trigger, value)`, so it should start
+    /// with `local trigger, value = ...` to name them, then return a list of
+    /// `{path, contents}` pairs to write, e.g.
+    /// `local trigger, value = ...; return {{"/sys/.../pwm1", "128"}}`.
+    Script {
+        trigger: String,
+        script: String,
+        #[serde(skip)]
+        func: OnceCell<mlua::Function>,
+        #[serde(rename = "on-trigger", default)]
+        semantics: TriggerSemantics,
+    },
+    /// Spawns an external command on each trigger match, e.g. to drive
+    /// `cpupower`/`ethtool`/a brightness tool that a plain file write can't
+    /// reach. `{value}` in `args` is substituted with the matched value.
+    Exec {
+        trigger: String,
+        command: String,
+        #[serde(default)]
+        args: Vec<String>,
+        values: HashMap<String, String>,
+        #[serde(default = "default_exec_timeout", with = "humantime_serde")]
+        timeout: Duration,
+        #[serde(rename = "on-trigger", default)]
+        semantics: TriggerSemantics,
     },
 }
 
+fn default_exec_timeout() -> Duration {
+    Duration::from_secs(5)
+}
+
 impl Action {
-    fn on_trigger(&self, t: &str, value: &str) -> Result<()> {
+    fn on_trigger(&self, t: &str, prev: Option<&str>, value: &str) -> Result<()> {
         match self {
-            Action::SimpleFile { trigger, file, values } => {
-                if t != *trigger {
+            Action::SimpleFile { trigger, file, values, semantics } => {
+                if t != *trigger || !semantics.should_fire(prev, value) {
                     return Ok(())
                 }
 
@@ -70,6 +200,80 @@ impl Action {
                     }
                 }
             },
+            Action::Script { trigger, script, func, semantics } => {
+                if t != *trigger || !semantics.should_fire(prev, value) {
+                    return Ok(())
+                }
+
+                let func = func.get_or_try_init(|| {
+                    Lua::new()
+                        .load(script.as_str())
+                        .set_name(trigger.as_str())
+                        .into_function()
+                        .context("Failed to load lua script for trigger")
+                })?;
+
+                let writes: Vec<(String, String)> = func.call((t, value))
+                    .context("Lua script evaluation failed")?;
+
+                if verbose() {
+                    println!("Script for trigger {:?} wrote: {:?}", trigger, writes);
+                }
+
+                for (path, contents) in writes {
+                    fs::write(&path, contents)
+                        .context("Failed to write lua script output")?;
+                }
+            },
+            Action::Exec { trigger, command, args, values, timeout, semantics } => {
+                if t != *trigger || !semantics.should_fire(prev, value) {
+                    return Ok(())
+                }
+
+                let Some(val) = values.get(value) else {
+                    if verbose() {
+                        eprintln!("Didn't find value for key {}", value);
+                    }
+                    return Ok(());
+                };
+
+                let argv: Vec<String> = args.iter().map(|a| a.replace("{value}", val)).collect();
+
+                if verbose() {
+                    eprintln!("Running {} {:?}", command, argv);
+                }
+
+                let mut child = Command::new(command)
+                    .args(&argv)
+                    .env("POWERED_TRIGGER", trigger)
+                    .env("POWERED_VALUE", val)
+                    .stdout(Stdio::null())
+                    .stderr(Stdio::piped())
+                    .spawn()
+                    .context("Failed to spawn exec action")?;
+
+                if let Some(stderr) = child.stderr.take() {
+                    std::thread::spawn(move || {
+                        for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+                            if verbose() {
+                                eprintln!("[exec] {}", line);
+                            }
+                        }
+                    });
+                }
+
+                match child.wait_timeout(*timeout).context("Failed to wait on exec action")? {
+                    Some(status) if status.success() => {},
+                    Some(status) => bail!("Command {} exited with {}", command, status),
+                    None => {
+                        let _ = child.kill();
+                        // `kill` only requests termination; reap the child
+                        // so it doesn't stick around as a zombie.
+                        let _ = child.wait();
+                        bail!("Command {} timed out after {:?}", command, timeout);
+                    }
+                }
+            },
         }
 
         Ok(())
@@ -80,26 +284,50 @@ impl Action {
 struct Config {
     action: Vec<Action>,
     trigger: Vec<Trigger>,
+    /// Whether the daemon's initial poll of each trigger (at startup or
+    /// after a config reload) is allowed to dispatch actions at all. Actions
+    /// still see the baseline value afterwards; this only gates that first
+    /// pass, so e.g. a fan isn't forced to max just because of a restart.
+    #[serde(rename = "run-on-start", default = "default_true")]
+    run_on_start: bool,
+}
+
+fn default_true() -> bool {
+    true
 }
 
 impl Config {
-    fn on_trigger(&self, trig: &str, value: &str) -> Result<()> {
+    fn on_trigger(&self, trig: &str, prev: Option<&str>, value: &str) -> Result<()> {
         for action in self.action.iter() {
-            action.on_trigger(trig, value)?;
+            action.on_trigger(trig, prev, value)?;
         }
         Ok(())
     }
 }
 
-struct TriggerHandler<'a> {
-    trigger: &'a Trigger,
+struct TriggerHandler {
+    trigger: Trigger,
     last_access: Option<Instant>,
-    cached_val: Option<&'a String>,
+    cached_val: Option<String>,
 }
 
-impl<'a> TriggerHandler<'a> {
-    fn new<'b>(trigger: &'a Trigger, inotify: &'b Inotify) -> Result<(Self, WatchDescriptor)> {
-        let desc = inotify.add_watch(&trigger.file, AddWatchFlags::IN_ACCESS)?;
+impl TriggerHandler {
+    /// Builds a handler for `trigger`. `SimpleFile` triggers get an inotify
+    /// watch and return its descriptor; `PollFile` triggers are driven by
+    /// the main loop's timer instead and return `None`. Takes `trigger` by
+    /// value so handlers can be torn down and rebuilt independently of the
+    /// `Config` they came from, which `reload` relies on.
+    fn new(trigger: Trigger, inotify: &Inotify) -> Result<(Self, Option<WatchDescriptor>)> {
+        let desc = match trigger.kind {
+            TriggerKind::SimpleFile => Some(inotify.add_watch(&trigger.file, AddWatchFlags::IN_ACCESS)?),
+            TriggerKind::PollFile => {
+                // Fail here, like `add_watch` does for `SimpleFile`, so a
+                // missing file rejects the config instead of the first poll.
+                fs::metadata(&trigger.file)
+                    .with_context(|| format!("poll-file trigger file {} not found", trigger.file.display()))?;
+                None
+            }
+        };
 
         Ok((Self {
             last_access: None,
@@ -110,25 +338,83 @@ impl<'a> TriggerHandler<'a> {
     fn name(&self) -> &str {
         &self.trigger.name
     }
-    fn poll_and_name(&mut self) -> Result<(Option<&str>, &str)> {
+
+    /// Look up the mapped value for `raw` according to the trigger's
+    /// `value-type`: an exact key for `string` (and `boolean`, which is
+    /// just a type-checked `true`/`false` key), or the first `value-map`
+    /// range expression that the parsed number falls into for `integer`
+    /// and `float`.
+    fn matched_value(&self, raw: &str) -> Option<&str> {
+        match self.trigger.value_type {
+            ValueType::String => self.trigger.map.get(raw).map(String::as_str),
+            ValueType::Boolean => {
+                let parsed: bool = match raw.parse() {
+                    Ok(v) => v,
+                    Err(_) => {
+                        eprintln!("Warning: failed to parse {:?} as boolean in trigger {}", raw, self.trigger.name);
+                        return None;
+                    }
+                };
+                self.trigger.map.get(if parsed { "true" } else { "false" }).map(String::as_str)
+            }
+            ValueType::Integer => {
+                let parsed: i64 = match raw.parse() {
+                    Ok(v) => v,
+                    Err(_) => {
+                        eprintln!("Warning: failed to parse {:?} as integer in trigger {}", raw, self.trigger.name);
+                        return None;
+                    }
+                };
+                self.trigger.map.iter().find_map(|(k, v)| {
+                    // A plain-integer key is compared directly in the integer
+                    // domain, without ever going through `RangeExpr`'s f64
+                    // parsing: round-tripping a key like a microamp or byte
+                    // counter through f64 can lose precision past 2^53.
+                    if let Ok(exact) = k.trim().parse::<i64>() {
+                        return (exact == parsed).then(|| v.as_str());
+                    }
+                    let r = RangeExpr::parse(k)?;
+                    r.matches(parsed as f64).then(|| v.as_str())
+                })
+            }
+            ValueType::Float => {
+                let parsed: f64 = match raw.parse() {
+                    Ok(v) => v,
+                    Err(_) => {
+                        eprintln!("Warning: failed to parse {:?} as float in trigger {}", raw, self.trigger.name);
+                        return None;
+                    }
+                };
+                self.trigger.map.iter().find_map(|(k, v)| {
+                    RangeExpr::parse(k).filter(|r| r.matches(parsed)).map(|_| v.as_str())
+                })
+            }
+        }
+    }
+
+    /// Re-reads the trigger's file and returns `(previous, current, name)`.
+    /// Unlike the old change-only behavior, `current` is always returned
+    /// when a value maps successfully, regardless of whether it differs
+    /// from `previous` — callers need both so each action's trigger
+    /// semantics (`on-change`/`on-enter`/`always`) can decide whether to fire.
+    /// Within 50ms of the last read the file isn't re-read: the cached value
+    /// comes back as `previous` and `current` is `None`.
+    fn poll_and_name(&mut self) -> Result<(Option<String>, Option<String>, &str)> {
         if self.last_access.is_some_and(|instant| instant.elapsed() < Duration::from_millis(50)) {
-            return Ok((None, &self.trigger.name));
+            return Ok((self.cached_val.clone(), None, &self.trigger.name));
         }
 
         let raw = fs::read_to_string(&self.trigger.file)?;
         self.last_access = Some(Instant::now());
-        let val = self.trigger.map.get(raw.trim());
+        let val = self.matched_value(raw.trim()).map(str::to_string);
 
         if val.is_none() {
             eprintln!("Warning: No value map for {} in trigger {}", raw, self.trigger.name);
         }
 
-        if val != self.cached_val {
-            self.cached_val = val;
-            Ok((self.cached_val.map(|s| s.as_str()), &self.trigger.name))
-        } else {
-            Ok((None, &self.trigger.name))
-        }
+        let prev = std::mem::replace(&mut self.cached_val, val.clone());
+
+        Ok((prev, val, &self.trigger.name))
     }
 }
 
@@ -140,58 +426,233 @@ struct Args {
     cfg: PathBuf,
 }
 
-fn main() -> Result<()> {
-    let args = Args::parse();
-    VERBOSE.store(args.verbose, Ordering::SeqCst);
+/// A `PollFile` trigger plus the state needed to drive it from the main
+/// loop's epoll timeout instead of an inotify event.
+struct PollEntry {
+    handler: TriggerHandler,
+    interval: Duration,
+    next: Instant,
+}
 
-    println!("Got args: {:#?}", args);
+/// Runs a freshly-polled/woken trigger's result through the config, logging
+/// and reporting errors the same way for both the inotify and poll paths.
+/// Forwards `prev`/`value` to `Config::on_trigger` whenever `value` is
+/// `Some`, even when they're equal, so `always` semantics can see every
+/// poll; a `None` value (unmapped, or a skipped re-read) dispatches nothing.
+fn dispatch(cfg: &Config, name: &str, prev: Option<&str>, value: Option<&str>) {
+    if let Some(val) = value {
+        if verbose() {
+            println!("Trigger {:?} result: {:?}", name, val);
+        }
+        if let Err(e) = cfg.on_trigger(name, prev, val) {
+            eprintln!("{e:#}");
+        }
+    }
+}
+
+/// Builds trigger state (inotify watches + poll timers) for `triggers`.
+/// `existing` is the currently-live trigger map, consulted (never mutated)
+/// because `inotify_add_watch` returns the *same* watch descriptor, rather
+/// than a new one, for a `file` that's already watched on this inotify
+/// instance — e.g. an unchanged `SimpleFile` trigger across a reload. If a
+/// handler fails to build partway through (e.g. a `poll-file` trigger
+/// missing `poll-interval`), watches already added for this attempt are
+/// removed again before returning the error, but only those that aren't
+/// also keys of `existing`: removing a reused watch here would rip it out
+/// from under the caller's still-live trigger map.
+fn build_triggers(
+    triggers: Vec<Trigger>,
+    inotify: &Inotify,
+    existing: &HashMap<WatchDescriptor, TriggerHandler>,
+) -> Result<(HashMap<WatchDescriptor, TriggerHandler>, Vec<PollEntry>)> {
+    let mut trigger_map = HashMap::new();
+    let mut poll_entries = Vec::new();
+
+    let result: Result<()> = (|| {
+        for trig in triggers {
+            let poll_interval = trig.poll_interval;
+            let (handler, desc) = TriggerHandler::new(trig, inotify)?;
+
+            match desc {
+                Some(watch) => {
+                    trigger_map.insert(watch, handler);
+                }
+                None => {
+                    let interval = poll_interval
+                        .context("poll-file trigger is missing poll-interval")?;
+                    poll_entries.push(PollEntry {
+                        handler,
+                        interval,
+                        next: Instant::now() + interval,
+                    });
+                }
+            }
+        }
+        Ok(())
+    })();
+
+    if let Err(e) = result {
+        for wd in trigger_map.keys() {
+            if existing.contains_key(wd) {
+                continue;
+            }
+            if let Err(e) = inotify.rm_watch(*wd) {
+                eprintln!("Warning: failed to remove watch from an aborted reload: {e}");
+            }
+        }
+        return Err(e);
+    }
 
-    let cfg_str = fs::read_to_string(&args.cfg)
+    Ok((trigger_map, poll_entries))
+}
+
+/// Re-reads and re-parses the config file at `path`, then rebuilds trigger
+/// watches/timers via `build_triggers` into fresh collections. The existing
+/// `trigger_map`/`poll_entries` are only torn down and swapped once the
+/// whole rebuild has succeeded, so a parse error *or* a bad trigger (e.g. a
+/// `poll-file` missing `poll-interval`, or a `file` that no longer exists)
+/// leaves the previously-running watches/timers untouched and returns an
+/// error instead, so a broken edit never takes an active daemon down. Old
+/// watches are only removed if they're *not* reused by the new trigger set
+/// (see `build_triggers`) — an unchanged `SimpleFile` trigger keeps the same
+/// watch descriptor across the reload, and blindly removing it would leave
+/// that trigger permanently deaf to inotify events.
+fn reload(
+    path: &Path,
+    inotify: &Inotify,
+    trigger_map: &mut HashMap<WatchDescriptor, TriggerHandler>,
+    poll_entries: &mut Vec<PollEntry>,
+) -> Result<Config> {
+    let cfg_str = fs::read_to_string(path)
         .context("Failed to read the config file")?;
-    let cfg: Config = toml::from_str(&cfg_str)
+    let mut cfg: Config = toml::from_str(&cfg_str)
         .context("Failed to deserialize the config file")?;
 
-    println!("Got config: {:#?}", cfg);
+    let (new_trigger_map, new_poll_entries) =
+        build_triggers(std::mem::take(&mut cfg.trigger), inotify, trigger_map)?;
 
-    let mut trigger_map = HashMap::new();
+    for wd in trigger_map.keys() {
+        if new_trigger_map.contains_key(wd) {
+            continue;
+        }
+        if let Err(e) = inotify.rm_watch(*wd) {
+            eprintln!("Warning: failed to remove stale watch: {e}");
+        }
+    }
+    *trigger_map = new_trigger_map;
+    *poll_entries = new_poll_entries;
+
+    Ok(cfg)
+}
+
+/// Polls every trigger once, used both for the daemon's startup poll and to
+/// bring freshly-reloaded triggers up to date. Always caches the baseline
+/// value; only dispatches actions for it when `cfg.run_on_start` is set.
+fn fire_initial(
+    cfg: &Config,
+    trigger_map: &mut HashMap<WatchDescriptor, TriggerHandler>,
+    poll_entries: &mut [PollEntry],
+) -> Result<()> {
+    for handler in trigger_map.values_mut() {
+        let (prev, value, name) = handler.poll_and_name()?;
+        if verbose() && value.is_some() {
+            println!("Init trigger {:?} result: {:?}", name, value);
+        }
+        if cfg.run_on_start {
+            dispatch(cfg, name, prev.as_deref(), value.as_deref());
+        }
+    }
+    for entry in poll_entries.iter_mut() {
+        let (prev, value, name) = entry.handler.poll_and_name()?;
+        if verbose() && value.is_some() {
+            println!("Init trigger {:?} result: {:?}", name, value);
+        }
+        if cfg.run_on_start {
+            dispatch(cfg, name, prev.as_deref(), value.as_deref());
+        }
+    }
+    Ok(())
+}
+
+fn main() -> Result<()> {
+    let args = Args::parse();
+    VERBOSE.store(args.verbose, Ordering::SeqCst);
+
+    println!("Got args: {:#?}", args);
 
     let inotify = Inotify::init(InitFlags::empty())
         .context("Failed to initialize an inotify instance")?;
-    for trig in &cfg.trigger {
-        let (mut handler, watch) = TriggerHandler::new(trig, &inotify)?;
-        let (value, name) = handler.poll_and_name()?;
 
-        if let Some(val) = value {
-            if verbose() {
-                println!("Init trigger {:?} result: {:?}", name, value);
-            }
-            if let Err(e) = cfg.on_trigger(name, &val) {
-                eprintln!("{e:#}");
-            }
-        }
+    let mut trigger_map = HashMap::new();
+    let mut poll_entries = Vec::new();
+    let mut cfg = reload(&args.cfg, &inotify, &mut trigger_map, &mut poll_entries)?;
 
-        trigger_map.insert(watch, handler);
-    }
+    println!("Got config: {:#?}", cfg);
+
+    fire_initial(&cfg, &mut trigger_map, &mut poll_entries)?;
+
+    let cfg_watch = inotify
+        .add_watch(&args.cfg, AddWatchFlags::IN_MODIFY | AddWatchFlags::IN_CLOSE_WRITE)
+        .context("Failed to watch the config file")?;
+
+    let epoll = Epoll::new(EpollCreateFlags::empty())
+        .context("Failed to create an epoll instance")?;
+    epoll
+        .add(&inotify, EpollEvent::new(EpollFlags::EPOLLIN, inotify.as_fd().as_raw_fd() as u64))
+        .context("Failed to register the inotify fd with epoll")?;
 
     loop {
-        let events = inotify.read_events().unwrap();
-        for ev in &events {
-            if verbose() {
-                println!("Processing event: {:#?}", ev);
+        let timeout = match poll_entries.iter().map(|e| e.next).min() {
+            Some(deadline) => {
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                // A `poll-interval` long enough to overflow `EpollTimeout`
+                // should wait as long as representable, not busy-loop: clamp
+                // to `i32::MAX` milliseconds instead of falling back to zero.
+                EpollTimeout::try_from(remaining).unwrap_or_else(|_| {
+                    EpollTimeout::try_from(Duration::from_millis(i32::MAX as u64))
+                        .unwrap_or(EpollTimeout::ZERO)
+                })
             }
+            None => EpollTimeout::NONE,
+        };
 
-            if let Some(handler) = trigger_map.get_mut(&ev.wd) {
-                let (value, name) = handler.poll_and_name()?;
-                if let Some(val) = value {
-                    if verbose() {
-                        println!("Trigger {:?} result: {:?}", name, value);
-                    }
-                    if let Err(e) = cfg.on_trigger(name, &val) {
-                        eprintln!("{e:#}");
+        let mut events = [EpollEvent::empty()];
+        let woken = epoll.wait(&mut events, timeout)
+            .context("epoll_wait failed")?;
+
+        if woken > 0 {
+            let events = inotify.read_events().unwrap();
+            for ev in &events {
+                if verbose() {
+                    println!("Processing event: {:#?}", ev);
+                }
+
+                if ev.wd == cfg_watch {
+                    match reload(&args.cfg, &inotify, &mut trigger_map, &mut poll_entries) {
+                        Ok(new_cfg) => {
+                            println!("Reloaded config from {}", args.cfg.display());
+                            cfg = new_cfg;
+                            fire_initial(&cfg, &mut trigger_map, &mut poll_entries)?;
+                        }
+                        Err(e) => eprintln!("Failed to reload config, keeping the previous one running: {e:#}"),
                     }
+                    continue;
+                }
+
+                if let Some(handler) = trigger_map.get_mut(&ev.wd) {
+                    let (prev, value, name) = handler.poll_and_name()?;
+                    dispatch(&cfg, name, prev.as_deref(), value.as_deref());
                 }
             }
         }
+
+        let now = Instant::now();
+        for entry in poll_entries.iter_mut() {
+            if entry.next <= now {
+                let (prev, value, name) = entry.handler.poll_and_name()?;
+                dispatch(&cfg, name, prev.as_deref(), value.as_deref());
+                entry.next = now + entry.interval;
+            }
+        }
     }
-    Ok(())
 }